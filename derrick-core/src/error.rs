@@ -0,0 +1,95 @@
+use std::fmt;
+
+use crate::migrations::migration::Migration;
+
+/// The top-level error type for derrick.
+#[derive(Debug)]
+pub enum Error {
+    /// A query against the database failed other than a migration's
+    /// own SQL (e.g. checking, reading or writing the history table,
+    /// or beginning or committing a migration's transaction).
+    Database(DatabaseError),
+    /// A query failed while applying or reverting a specific
+    /// migration.
+    Migration { version: i64, source: DatabaseError },
+    /// The migrations, or a request about them, are invalid: the
+    /// source migrations failed `validate_source` (a duplicate version
+    /// or a checksum mismatch; gaps between versions are not checked),
+    /// a revert found no down migration or no matching source, or a
+    /// database URL has an unsupported scheme.
+    Validation(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Database(source) => write!(f, "database error: {source}"),
+            Error::Migration { version, source } => {
+                write!(f, "migration {version} failed: {source}")
+            }
+            Error::Validation(message) => write!(f, "invalid migrations: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// A database-layer error, wrapping whatever the backend's driver
+/// reported.
+///
+/// This is boxed rather than tied to a particular driver's error type
+/// so that `derrick_core` stays backend-agnostic.
+#[derive(Debug)]
+pub struct DatabaseError(Box<dyn std::error::Error + Send + Sync>);
+
+impl fmt::Display for DatabaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for DatabaseError {}
+
+impl<E> From<E> for DatabaseError
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    fn from(err: E) -> Self {
+        Self(Box::new(err))
+    }
+}
+
+/// Converts a driver-level `Result` into one carrying `derrick_core`'s
+/// `Error`, so every backend reports failures the same way.
+pub trait IntoError<T> {
+    /// Map a failure to `Error::Database`.
+    fn into_error(self) -> Result<T, Error>;
+
+    /// Map a failure to `Error::Database`, discarding the success
+    /// value.
+    fn into_error_void(self) -> Result<(), Error>;
+
+    /// Map a failure to `Error::Migration`, attributing it to the
+    /// given migration.
+    fn into_error_with(self, migration: &Migration) -> Result<T, Error>;
+}
+
+impl<T, E> IntoError<T> for Result<T, E>
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    fn into_error(self) -> Result<T, Error> {
+        self.map_err(|e| Error::Database(DatabaseError::from(e)))
+    }
+
+    fn into_error_void(self) -> Result<(), Error> {
+        self.map(|_| ()).map_err(|e| Error::Database(DatabaseError::from(e)))
+    }
+
+    fn into_error_with(self, migration: &Migration) -> Result<T, Error> {
+        self.map_err(|e| Error::Migration {
+            version: migration.version,
+            source: DatabaseError::from(e),
+        })
+    }
+}
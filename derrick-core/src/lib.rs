@@ -0,0 +1,5 @@
+pub mod error;
+pub mod migrations;
+pub mod prelude;
+pub mod reexport;
+pub mod types;
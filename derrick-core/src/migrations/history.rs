@@ -0,0 +1,52 @@
+use crate::migrations::migration::AppliedMigration;
+
+/// Additional options used to construct a `HistoryTable`.
+///
+/// This is minimal in that it only carries the table name, but gives
+/// backends a single, non-breaking place to grow new options.
+#[derive(Debug, Clone)]
+pub struct HistoryTableOptions {
+    name: String,
+}
+
+impl HistoryTableOptions {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self { name: name.into() }
+    }
+
+    pub fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+/// A backend-specific schema history table.
+pub trait HistoryTable
+where
+    Self: Send + Sync,
+{
+    /// Build from the options common to every backend.
+    fn new(options: &HistoryTableOptions) -> Self;
+
+    /// The table name.
+    fn table(&self) -> String;
+
+    /// DDL to create the table if it does not already exist.
+    fn create_if_not_exists_query(&self) -> String;
+
+    /// Query to read every row currently in the table.
+    fn select_star_from_query(&self) -> String;
+
+    /// Query to insert a newly applied migration, with the backend's
+    /// own placeholder style.
+    fn insert_into_query(&self, applied: &AppliedMigration) -> String;
+}
+
+/// A row already present in the history table.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ExistingMigration {
+    pub version: i64,
+    pub description: String,
+    pub content: String,
+    pub checksum: String,
+    pub duration_ms: i64,
+}
@@ -1,5 +1,5 @@
 use super::history::{ExistingMigration, HistoryTable};
-use super::migration::{AppliedMigration, Migration};
+use super::migration::{AppliedMigration, FutureMigration, Migration};
 use super::source::MigrationSource;
 use crate::error::Error;
 
@@ -27,6 +27,10 @@ where
     /// Additional data needed to initialize.
     type Init: Clone + Send + Sync;
 
+    /// The live connection handed to a Rust-defined (function)
+    /// migration so it can run arbitrary code against the database.
+    type Conn: Send;
+
     /// Create a new value.
     fn initialize(
         db_url: String,
@@ -36,6 +40,34 @@ where
     where
         Self: Sized;
 
+    /// Create the target database if it does not already exist.
+    ///
+    /// This runs before `initialize` opens the real pool, removing the
+    /// manual `createdb` step from first-run and CI setups.  The
+    /// default is a no-op for backends where it does not apply.
+    fn create_database_if_not_exists(db_url: String) -> BoxFuture<'static, Result<(), Error>>
+    where
+        Self: Sized,
+    {
+        Box::pin(async move {
+            let _ = db_url;
+            Ok(())
+        })
+    }
+
+    /// Drop the target database, for teardown in tests.
+    ///
+    /// The default is a no-op, mirroring `create_database_if_not_exists`.
+    fn drop_database(db_url: String) -> BoxFuture<'static, Result<(), Error>>
+    where
+        Self: Sized,
+    {
+        Box::pin(async move {
+            let _ = db_url;
+            Ok(())
+        })
+    }
+
     /// Create the history table if it does not exist.
     fn check_history_table(&mut self) -> BoxFuture<'_, Result<(), Error>>;
 
@@ -61,6 +93,55 @@ where
         migration: &'a Migration,
     ) -> BoxFuture<'a, Result<AppliedMigration, Error>>;
 
+    /// Apply a Rust-defined migration and update history outside of a
+    /// transaction.
+    ///
+    /// The migration's function is handed the live connection so it can
+    /// perform work a static SQL string cannot express.
+    fn apply_fn_no_tx<'a, 'c: 'a>(
+        &'c mut self,
+        migration: FutureMigration<Self::Conn>,
+    ) -> BoxFuture<'a, Result<AppliedMigration, Error>>;
+
+    /// Apply a Rust-defined migration and update history in a
+    /// transaction.
+    fn apply_fn_tx<'a, 'c: 'a>(
+        &'c mut self,
+        migration: FutureMigration<Self::Conn>,
+    ) -> BoxFuture<'a, Result<AppliedMigration, Error>>;
+
+    /// Revert a migration and update history outside of a
+    /// transaction.
+    ///
+    /// This runs the migration's down statements and then removes
+    /// its row from the history table.  A migration with no down
+    /// statements is an error and its row is left in place.
+    fn revert_no_tx<'a, 'c: 'a>(
+        &'c mut self,
+        migration: &'a Migration,
+    ) -> BoxFuture<'a, Result<(), Error>>;
+
+    /// Revert a migration and update history in a transaction.
+    fn revert_tx<'a, 'c: 'a>(
+        &'c mut self,
+        migration: &'a Migration,
+    ) -> BoxFuture<'a, Result<(), Error>>;
+
+    /// Acquire a lock for the duration of a migration batch.
+    ///
+    /// The default is a no-op; backends that support it should take a
+    /// lock so concurrent runs against the same database do not race on
+    /// the history table.  `unlock` is always called afterwards, even
+    /// when a migration errors.
+    fn lock(&mut self) -> BoxFuture<'_, Result<(), Error>> {
+        Box::pin(async move { Ok(()) })
+    }
+
+    /// Release the lock taken by `lock`.
+    fn unlock(&mut self) -> BoxFuture<'_, Result<(), Error>> {
+        Box::pin(async move { Ok(()) })
+    }
+
     /// Get the most recent applied migration version.
     fn current_version(&mut self) -> BoxFuture<'_, Result<Option<i64>, Error>> {
         Box::pin(async move {
@@ -90,6 +171,68 @@ where
         }
     }
 
+    /// Apply a Rust-defined migration.
+    fn apply_fn<'a, 'c: 'a>(
+        &'c mut self,
+        migration: FutureMigration<Self::Conn>,
+    ) -> BoxFuture<'a, Result<AppliedMigration, Error>> {
+        if migration.no_tx {
+            self.apply_fn_no_tx(migration)
+        } else {
+            self.apply_fn_tx(migration)
+        }
+    }
+
+    /// Revert a migration.
+    fn revert<'a, 'c: 'a>(
+        &'c mut self,
+        migration: &'a Migration,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        if migration.no_tx {
+            self.revert_no_tx(migration)
+        } else {
+            self.revert_tx(migration)
+        }
+    }
+
+    /// Revert every applied migration newer than `target_version`,
+    /// most recent first.
+    ///
+    /// The down SQL needed to undo a migration is not stored in the
+    /// history table, so the source `migrations` are consulted to find
+    /// the revert for each applied version. If an applied version has
+    /// no matching source, reverting stops with an error instead of
+    /// skipping it: continuing on to older versions would leave a gap
+    /// in the history table with no way to undo it later.
+    fn revert_to<'a, 'c: 'a>(
+        &'c mut self,
+        target_version: i64,
+        migrations: &'a [Migration],
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let mut versions = self
+                .get_history_table()
+                .await?
+                .into_iter()
+                .map(|m| m.version)
+                .filter(|version| *version > target_version)
+                .collect::<Vec<_>>();
+            // Undo the most recently applied migration first.
+            versions.sort_unstable_by(|a, b| b.cmp(a));
+
+            for version in versions {
+                let migration = migrations.iter().find(|m| m.version == version).ok_or_else(|| {
+                    Error::Validation(format!(
+                        "applied migration {version} has no matching source migration to revert"
+                    ))
+                })?;
+                self.revert(migration).await?;
+            }
+
+            Ok(())
+        })
+    }
+
     /// Enforce rules about source migrations.
     fn validate_source(
         source: Vec<MigrationSource>,
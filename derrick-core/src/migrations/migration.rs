@@ -0,0 +1,114 @@
+use std::fmt;
+
+use futures_core::future::BoxFuture;
+
+use crate::error::Error;
+
+/// A single migration parsed from a source file, with its forward
+/// (and, optionally, reverse) SQL.
+#[derive(Debug, Clone)]
+pub struct Migration {
+    pub version: i64,
+    pub description: String,
+    /// The full text of the migration as it appears in the source
+    /// tree; stored in the history table as `content` so a diff
+    /// against what is on disk is possible later.
+    pub content: String,
+    /// Hex-encoded SHA-256 of `content`, stored alongside it so
+    /// `validate_source` can detect a source file edited after it was
+    /// applied.
+    pub checksum: String,
+    /// The up migration, run inside a transaction.
+    pub sql: String,
+    /// The up migration, split into individual statements, for
+    /// backends that cannot send more than one statement through a
+    /// prepared query and so must run it outside of a transaction.
+    pub statements: Vec<String>,
+    /// The down migration, run inside a transaction, if one was
+    /// provided.
+    pub down_sql: Option<String>,
+    /// The down migration, split into individual statements, if one
+    /// was provided.
+    pub down_statements: Option<Vec<String>>,
+    /// Whether this migration must run outside of a transaction.
+    pub no_tx: bool,
+}
+
+impl Migration {
+    /// Build the row to store in the history table once this
+    /// migration has been applied.
+    pub fn new_applied(&self, duration_ms: i64) -> AppliedMigration {
+        AppliedMigration {
+            version: self.version,
+            description: self.description.clone(),
+            content: self.content.clone(),
+            checksum: self.checksum.clone(),
+            duration_ms,
+        }
+    }
+}
+
+/// A migration whose body is a Rust function rather than static SQL,
+/// for changes a SQL string cannot express (backfilling columns,
+/// rewriting BLOBs, conditional DDL).
+///
+/// `C` is the backend's live connection type (`Migrate::Conn`), which
+/// is handed to `f` so the migration can run arbitrary code against
+/// the database.
+pub struct FutureMigration<C> {
+    pub version: i64,
+    pub description: String,
+    pub content: String,
+    pub checksum: String,
+    pub no_tx: bool,
+    pub f: Box<dyn FnOnce(&mut C) -> BoxFuture<'static, Result<(), Error>> + Send>,
+}
+
+impl<C> FutureMigration<C> {
+    /// Build the row to store in the history table once this
+    /// migration has been applied.
+    pub fn new_applied(&self, duration_ms: i64) -> AppliedMigration {
+        AppliedMigration {
+            version: self.version,
+            description: self.description.clone(),
+            content: self.content.clone(),
+            checksum: self.checksum.clone(),
+            duration_ms,
+        }
+    }
+}
+
+impl<C> fmt::Debug for FutureMigration<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FutureMigration")
+            .field("version", &self.version)
+            .field("description", &self.description)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A migration that has been applied and recorded in the history
+/// table.
+#[derive(Debug, Clone)]
+pub struct AppliedMigration {
+    pub version: i64,
+    pub description: String,
+    pub content: String,
+    pub checksum: String,
+    pub duration_ms: i64,
+}
+
+/// Compute the hex-encoded SHA-256 checksum of a migration's source
+/// text.
+///
+/// This lives in `derrick_core`, rather than being recomputed
+/// per-backend, so that every backend's history table and
+/// `validate_source` agree on the same digest.
+pub fn checksum(content: &str) -> String {
+    use sha2::{Digest, Sha256};
+
+    Sha256::digest(content.as_bytes())
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
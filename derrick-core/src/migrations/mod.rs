@@ -0,0 +1,4 @@
+pub mod history;
+pub mod migrate;
+pub mod migration;
+pub mod source;
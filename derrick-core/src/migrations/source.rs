@@ -0,0 +1,30 @@
+use super::migration::{FutureMigration, Migration};
+
+/// A migration discovered from the source tree (a `.sql` or `.rs`
+/// file), before it has ever been run against any database.
+#[derive(Debug, Clone)]
+pub struct MigrationSource {
+    pub version: i64,
+    pub description: String,
+    pub content: String,
+}
+
+impl From<&Migration> for MigrationSource {
+    fn from(migration: &Migration) -> Self {
+        Self {
+            version: migration.version,
+            description: migration.description.clone(),
+            content: migration.content.clone(),
+        }
+    }
+}
+
+impl<C> From<&FutureMigration<C>> for MigrationSource {
+    fn from(migration: &FutureMigration<C>) -> Self {
+        Self {
+            version: migration.version,
+            description: migration.description.clone(),
+            content: migration.content.clone(),
+        }
+    }
+}
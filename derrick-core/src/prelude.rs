@@ -0,0 +1,3 @@
+pub use crate::error::IntoError;
+pub use crate::migrations::history::HistoryTable;
+pub use crate::migrations::migrate::Migrate;
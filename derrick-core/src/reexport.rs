@@ -0,0 +1 @@
+pub use futures_core::future::BoxFuture;
@@ -0,0 +1,3 @@
+pub use crate::migrations::history::{ExistingMigration, HistoryTableOptions};
+pub use crate::migrations::migration::{checksum, AppliedMigration, FutureMigration, Migration};
+pub use crate::migrations::source::MigrationSource;
@@ -1,20 +1,39 @@
-use derrick_core::error::{DatabaseError, Error};
+use derrick_core::error::Error;
 use derrick_core::prelude::*;
 use derrick_core::reexport::BoxFuture;
 use derrick_core::types::{
-    AppliedMigration, ExistingMigration, HistoryTableOptions, Migration, MigrationSource,
+    AppliedMigration, ExistingMigration, FutureMigration, HistoryTableOptions, Migration,
+    MigrationSource,
 };
-use sqlx::{postgres, Acquire, Executor, PgPool, Postgres};
+use sqlx::pool::PoolConnection;
+use sqlx::{postgres, Acquire, ConnectOptions, Connection, Executor, PgPool, Postgres};
+use std::hash::{Hash, Hasher};
 use std::time::Instant;
 
 use crate::migrate::pg::PgHistoryTableOptions;
 use crate::migrate::validate::Validate;
 
 /// A `Migrate` for `sqlx::PgPool`.
-#[derive(Clone)]
 pub struct SqlxPgMigrate {
     pool: PgPool,
     history_table: SqlxPgHistoryTable,
+    /// The connection holding the session advisory lock, if any.
+    /// Advisory locks are per-session, so the lock has to be held on a
+    /// single dedicated connection for the life of the batch rather
+    /// than on arbitrary pooled connections.
+    lock_conn: Option<PoolConnection<Postgres>>,
+}
+
+// Cloning a `SqlxPgMigrate` does not carry the lock: the lock belongs
+// to the session that acquired it, not to a logical copy of the value.
+impl Clone for SqlxPgMigrate {
+    fn clone(&self) -> Self {
+        Self {
+            pool: self.pool.clone(),
+            history_table: self.history_table.clone(),
+            lock_conn: None,
+        }
+    }
 }
 
 /// Additional options to create the `Migrate`.
@@ -47,8 +66,8 @@ impl HistoryTable for SqlxPgHistoryTable {
     fn insert_into_query(&self, _: &AppliedMigration) -> String {
         let sql = format!(
             "
-INSERT INTO {}(version, description, content, duration_ms)
-  VALUES ($1, $2, $3, $4);",
+INSERT INTO {}(version, description, content, checksum, duration_ms)
+  VALUES ($1, $2, $3, $4, $5);",
             self.name(),
         );
 
@@ -60,6 +79,8 @@ impl Migrate for SqlxPgMigrate {
     type History = SqlxPgHistoryTable;
     // We don't need anything more to initialize.
     type Init = ();
+    // Rust-defined migrations receive a `PgConnection` directly.
+    type Conn = postgres::PgConnection;
 
     fn initialize(
         db_url: String,
@@ -76,13 +97,119 @@ impl Migrate for SqlxPgMigrate {
         })
     }
 
+    fn lock(&mut self) -> BoxFuture<'_, Result<(), Error>> {
+        let key = self.advisory_key();
+        Box::pin(async move {
+            // The lock is per-session, so it has to live on a dedicated
+            // connection held for the whole batch rather than on an
+            // arbitrary pooled connection.
+            let mut conn = self.pool().acquire().await.into_error()?;
+            log::debug!("acquiring advisory lock {key}");
+            sqlx::query("SELECT pg_advisory_lock($1)")
+                .bind(key)
+                .execute(&mut *conn)
+                .await
+                .into_error()?;
+            self.lock_conn = Some(conn);
+
+            Ok(())
+        })
+    }
+
+    fn unlock(&mut self) -> BoxFuture<'_, Result<(), Error>> {
+        let key = self.advisory_key();
+        Box::pin(async move {
+            if let Some(mut conn) = self.lock_conn.take() {
+                log::debug!("releasing advisory lock {key}");
+                sqlx::query("SELECT pg_advisory_unlock($1)")
+                    .bind(key)
+                    .execute(&mut *conn)
+                    .await
+                    .into_error()?;
+            }
+
+            Ok(())
+        })
+    }
+
+    fn create_database_if_not_exists(db_url: String) -> BoxFuture<'static, Result<(), Error>> {
+        Box::pin(async move {
+            let opts = db_url.parse::<postgres::PgConnectOptions>().into_error()?;
+            let database = opts.get_database().unwrap_or_default().to_string();
+
+            // Connect to a maintenance database to create the target.
+            // `createdb` connects to `template1` when the target is
+            // itself named `postgres`, so mirror that here.
+            let maintenance = if database == "postgres" {
+                "template1"
+            } else {
+                "postgres"
+            };
+            let mut conn = opts.database(maintenance).connect().await.into_error()?;
+
+            let exists = sqlx::query("SELECT 1 FROM pg_database WHERE datname = $1")
+                .bind(&database)
+                .fetch_optional(&mut conn)
+                .await
+                .into_error()?
+                .is_some();
+
+            if !exists {
+                log::debug!("creating database {database}");
+                // `CREATE DATABASE` cannot be parameterized, so the name
+                // is quoted as an identifier.
+                conn.execute(sqlx::raw_sql(&format!("CREATE DATABASE \"{database}\"")))
+                    .await
+                    .into_error()?;
+            }
+
+            conn.close().await.into_error()?;
+
+            Ok(())
+        })
+    }
+
+    fn drop_database(db_url: String) -> BoxFuture<'static, Result<(), Error>> {
+        Box::pin(async move {
+            let opts = db_url.parse::<postgres::PgConnectOptions>().into_error()?;
+            let database = opts.get_database().unwrap_or_default().to_string();
+
+            let maintenance = if database == "postgres" {
+                "template1"
+            } else {
+                "postgres"
+            };
+            let mut conn = opts.database(maintenance).connect().await.into_error()?;
+
+            log::debug!("dropping database {database}");
+            conn.execute(sqlx::raw_sql(&format!(
+                "DROP DATABASE IF EXISTS \"{database}\""
+            )))
+            .await
+            .into_error()?;
+
+            conn.close().await.into_error()?;
+
+            Ok(())
+        })
+    }
+
     fn check_history_table(&mut self) -> BoxFuture<'_, Result<(), Error>> {
         let history = self.history_table();
-        let sql = history.create_if_not_exists_query().clone();
+        let create_sql = history.create_if_not_exists_query().clone();
+        let add_checksum_sql = history.add_checksum_column_query();
 
         Box::pin(async move {
             log::debug!("running `create table if exists` query");
-            sqlx::query(&sql)
+            sqlx::query(&create_sql)
+                .execute(self.pool())
+                .await
+                .into_error_void()?;
+
+            // A history table from before `checksum` existed needs the
+            // column added; this is a no-op once the table is current.
+            log::debug!("ensuring the `checksum` column exists");
+            sqlx::query(&add_checksum_sql)
                 .execute(self.pool())
                 .await
                 .into_error_void()
@@ -117,6 +244,7 @@ impl Migrate for SqlxPgMigrate {
                 .bind(applied.version)
                 .bind(applied.description.clone())
                 .bind(applied.content.clone())
+                .bind(applied.checksum.clone())
                 .bind(applied.duration_ms)
                 .execute(self.pool())
                 .await
@@ -187,6 +315,62 @@ impl Migrate for SqlxPgMigrate {
                 .bind(applied.version)
                 .bind(applied.description.clone())
                 .bind(applied.content.clone())
+                .bind(applied.checksum.clone())
+                .bind(applied.duration_ms)
+                .execute(&mut *conn)
+                .await
+                .into_error_void()?;
+
+            tx.commit().await.into_error()?;
+
+            Ok(applied)
+        })
+    }
+
+    fn apply_fn_no_tx<'a, 'c: 'a>(
+        &'c mut self,
+        migration: FutureMigration<Self::Conn>,
+    ) -> BoxFuture<'a, Result<AppliedMigration, Error>> {
+        Box::pin(async move {
+            let mut conn = self.pool().acquire().await.into_error()?;
+            let now = Instant::now();
+
+            log::debug!("applying migration {}...", migration.version);
+            (migration.f)(&mut conn).await?;
+            let duration_ms = now.elapsed().as_millis() as i64;
+            let applied = migration.new_applied(duration_ms);
+
+            log::debug!("migration {} applied", migration.version);
+            self.insert_new_applied(&applied).await.into_error_void()?;
+
+            Ok(applied)
+        })
+    }
+
+    fn apply_fn_tx<'a, 'c: 'a>(
+        &'c mut self,
+        migration: FutureMigration<Self::Conn>,
+    ) -> BoxFuture<'a, Result<AppliedMigration, Error>> {
+        Box::pin(async move {
+            let mut tx = self.pool().begin().await.into_error()?;
+            let conn = tx.acquire().await.into_error()?;
+
+            let now = Instant::now();
+
+            log::debug!("applying migration {}...", migration.version);
+            (migration.f)(conn).await?;
+            let duration_ms = now.elapsed().as_millis() as i64;
+
+            let applied = migration.new_applied(duration_ms);
+            let history = self.history_table();
+            let insert_sql = history.insert_into_query(&applied).clone();
+
+            log::debug!("migration {} applied", migration.version);
+            sqlx::query(&insert_sql)
+                .bind(applied.version)
+                .bind(applied.description.clone())
+                .bind(applied.content.clone())
+                .bind(applied.checksum.clone())
                 .bind(applied.duration_ms)
                 .execute(&mut *conn)
                 .await
@@ -198,6 +382,76 @@ impl Migrate for SqlxPgMigrate {
         })
     }
 
+    fn revert_no_tx<'a, 'c: 'a>(
+        &'c mut self,
+        migration: &'a Migration,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let Some(statements) = migration.down_statements.clone() else {
+                return Err(Error::Validation(format!(
+                    "migration {} has no down migration to revert",
+                    migration.version
+                )));
+            };
+
+            log::debug!("reverting migration {}...", migration.version);
+            // As with `apply_no_tx`, each statement is sent individually
+            // with `sqlx::raw_sql` because a migration with more than one
+            // query cannot be sent as a prepared statement.
+            for statement in statements.iter() {
+                self.pool()
+                    .execute(sqlx::raw_sql(statement.as_ref()))
+                    .await
+                    .into_error_with(migration)?;
+            }
+
+            log::debug!("migration {} reverted", migration.version);
+            let history = self.history_table();
+            let delete_sql = history.delete_from_query();
+            sqlx::query(&delete_sql)
+                .bind(migration.version)
+                .execute(self.pool())
+                .await
+                .into_error_void()?;
+
+            Ok(())
+        })
+    }
+
+    fn revert_tx<'a, 'c: 'a>(
+        &'c mut self,
+        migration: &'a Migration,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let Some(sql) = migration.down_sql.clone() else {
+                return Err(Error::Validation(format!(
+                    "migration {} has no down migration to revert",
+                    migration.version
+                )));
+            };
+            let mut tx = self.pool().begin().await.into_error()?;
+            let conn = tx.acquire().await.into_error()?;
+
+            log::debug!("reverting migration {}...", migration.version);
+            conn.execute(sqlx::raw_sql(&sql))
+                .await
+                .into_error_with(migration)?;
+
+            log::debug!("migration {} reverted", migration.version);
+            let history = self.history_table();
+            let delete_sql = history.delete_from_query();
+            sqlx::query(&delete_sql)
+                .bind(migration.version)
+                .execute(&mut *conn)
+                .await
+                .into_error_void()?;
+
+            tx.commit().await.into_error()?;
+
+            Ok(())
+        })
+    }
+
     fn validate_source(
         source: Vec<MigrationSource>,
         history: Vec<ExistingMigration>,
@@ -211,9 +465,17 @@ impl SqlxPgMigrate {
         Self {
             pool,
             history_table,
+            lock_conn: None,
         }
     }
 
+    /// Derive a stable advisory-lock key from the history table name.
+    fn advisory_key(&self) -> i64 {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        self.history_table().name().hash(&mut hasher);
+        hasher.finish() as i64
+    }
+
     pub fn pool(&self) -> &PgPool {
         &self.pool
     }
@@ -231,4 +493,15 @@ impl SqlxPgHistoryTable {
     pub fn name(&self) -> String {
         self.name.clone()
     }
+
+    fn delete_from_query(&self) -> String {
+        format!("DELETE FROM {} WHERE version = $1;", self.name())
+    }
+
+    /// Add the `checksum` column to a history table that was created
+    /// before this column existed.
+    fn add_checksum_column_query(&self) -> String {
+        let pg_tbl = PgHistoryTableOptions::new(self.name());
+        pg_tbl.add_checksum_column_query()
+    }
 }
@@ -0,0 +1,363 @@
+use derrick_core::error::Error;
+use derrick_core::prelude::*;
+use derrick_core::reexport::BoxFuture;
+use derrick_core::types::{
+    AppliedMigration, ExistingMigration, FutureMigration, HistoryTableOptions, Migration,
+    MigrationSource,
+};
+use sqlx::{sqlite, Acquire, Executor, Sqlite, SqlitePool};
+use std::time::Instant;
+
+use crate::migrate::validate::Validate;
+
+/// A `Migrate` for `sqlx::SqlitePool`.
+#[derive(Clone)]
+pub struct SqlxSqliteMigrate {
+    pool: SqlitePool,
+    history_table: SqlxSqliteHistoryTable,
+}
+
+/// Additional options to create the `Migrate`.
+/// This is minimal in that it only has the history
+/// table.
+#[derive(Debug, Clone)]
+pub struct SqlxSqliteHistoryTable {
+    name: String,
+}
+
+impl HistoryTable for SqlxSqliteHistoryTable {
+    fn new(options: &HistoryTableOptions) -> Self {
+        Self::new(options.name())
+    }
+
+    fn table(&self) -> String {
+        self.name.clone()
+    }
+
+    fn create_if_not_exists_query(&self) -> String {
+        format!(
+            "
+CREATE TABLE IF NOT EXISTS {}(
+  version INTEGER PRIMARY KEY,
+  description TEXT NOT NULL,
+  content TEXT NOT NULL,
+  checksum TEXT NOT NULL,
+  duration_ms INTEGER NOT NULL
+);",
+            self.name(),
+        )
+    }
+
+    fn select_star_from_query(&self) -> String {
+        format!("SELECT * FROM {};", self.name())
+    }
+
+    fn insert_into_query(&self, _: &AppliedMigration) -> String {
+        format!(
+            "
+INSERT INTO {}(version, description, content, checksum, duration_ms)
+  VALUES (?, ?, ?, ?, ?);",
+            self.name(),
+        )
+    }
+}
+
+impl Migrate for SqlxSqliteMigrate {
+    type History = SqlxSqliteHistoryTable;
+    // We don't need anything more to initialize.
+    type Init = ();
+    // Rust-defined migrations receive a `SqliteConnection` directly.
+    type Conn = sqlite::SqliteConnection;
+
+    fn initialize(
+        db_url: String,
+        history: Self::History,
+        _: Self::Init,
+    ) -> BoxFuture<'static, Result<Self, Error>> {
+        Box::pin(async move {
+            let opts = db_url.parse::<sqlite::SqliteConnectOptions>().into_error()?;
+            let pool = sqlite::SqlitePoolOptions::new()
+                .connect_with(opts)
+                .await
+                .into_error()?;
+            Ok(SqlxSqliteMigrate::new(pool, history))
+        })
+    }
+
+    fn check_history_table(&mut self) -> BoxFuture<'_, Result<(), Error>> {
+        let history = self.history_table();
+        let sql = history.create_if_not_exists_query().clone();
+
+        Box::pin(async move {
+            log::debug!("running `create table if exists` query");
+            sqlx::query(&sql)
+                .execute(self.pool())
+                .await
+                .into_error_void()
+        })
+    }
+
+    fn get_history_table(&mut self) -> BoxFuture<'_, Result<Vec<ExistingMigration>, Error>> {
+        Box::pin(async move {
+            let history = self.history_table();
+            let sql = history.select_star_from_query();
+
+            log::debug!("running select query");
+            let rows = sqlx::query_as::<Sqlite, ExistingMigration>(&sql)
+                .fetch_all(self.pool())
+                .await
+                .into_error()?;
+
+            Ok(rows)
+        })
+    }
+
+    fn insert_new_applied<'a, 'c: 'a>(
+        &'c mut self,
+        applied: &'a AppliedMigration,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let history = self.history_table();
+            let sql = history.insert_into_query(applied);
+
+            log::debug!("running insert query");
+            sqlx::query(&sql)
+                .bind(applied.version)
+                .bind(applied.description.clone())
+                .bind(applied.content.clone())
+                .bind(applied.checksum.clone())
+                .bind(applied.duration_ms)
+                .execute(self.pool())
+                .await
+                .into_error()?;
+
+            Ok(())
+        })
+    }
+
+    fn apply_no_tx<'a, 'c: 'a>(
+        &'c mut self,
+        migration: &'a Migration,
+    ) -> BoxFuture<'a, Result<AppliedMigration, Error>> {
+        Box::pin(async move {
+            let statements = &migration.statements;
+            let now = Instant::now();
+
+            // SQLite cannot send multiple statements through a prepared
+            // query either, so each statement is run individually with
+            // `sqlx::raw_sql`, as in the Postgres backend.
+            log::debug!("applying migration {}...", migration.version);
+            for statement in statements.iter() {
+                self.pool()
+                    .execute(sqlx::raw_sql(statement.as_ref()))
+                    .await
+                    .into_error_with(migration)?;
+            }
+            let duration_ms = now.elapsed().as_millis() as i64;
+            let applied = migration.new_applied(duration_ms);
+
+            log::debug!("migration {} applied", migration.version);
+            self.insert_new_applied(&applied).await.into_error_void()?;
+
+            Ok(applied)
+        })
+    }
+
+    fn apply_tx<'a, 'c: 'a>(
+        &'c mut self,
+        migration: &'a Migration,
+    ) -> BoxFuture<'a, Result<AppliedMigration, Error>> {
+        Box::pin(async move {
+            let sql = migration.sql.to_string();
+            let mut tx = self.pool().begin().await.into_error()?;
+            let conn = tx.acquire().await.into_error()?;
+
+            let now = Instant::now();
+
+            log::debug!("applying migration {}...", migration.version);
+            conn.execute(sqlx::raw_sql(&sql))
+                .await
+                .into_error_with(migration)?;
+            let duration_ms = now.elapsed().as_millis() as i64;
+
+            let applied = migration.new_applied(duration_ms);
+            let history = self.history_table();
+            let insert_sql = history.insert_into_query(&applied).clone();
+
+            log::debug!("migration {} applied", migration.version);
+            sqlx::query(&insert_sql)
+                .bind(applied.version)
+                .bind(applied.description.clone())
+                .bind(applied.content.clone())
+                .bind(applied.checksum.clone())
+                .bind(applied.duration_ms)
+                .execute(&mut *conn)
+                .await
+                .into_error_void()?;
+
+            tx.commit().await.into_error()?;
+
+            Ok(applied)
+        })
+    }
+
+    fn apply_fn_no_tx<'a, 'c: 'a>(
+        &'c mut self,
+        migration: FutureMigration<Self::Conn>,
+    ) -> BoxFuture<'a, Result<AppliedMigration, Error>> {
+        Box::pin(async move {
+            let mut conn = self.pool().acquire().await.into_error()?;
+            let now = Instant::now();
+
+            log::debug!("applying migration {}...", migration.version);
+            (migration.f)(&mut conn).await?;
+            let duration_ms = now.elapsed().as_millis() as i64;
+            let applied = migration.new_applied(duration_ms);
+
+            log::debug!("migration {} applied", migration.version);
+            self.insert_new_applied(&applied).await.into_error_void()?;
+
+            Ok(applied)
+        })
+    }
+
+    fn apply_fn_tx<'a, 'c: 'a>(
+        &'c mut self,
+        migration: FutureMigration<Self::Conn>,
+    ) -> BoxFuture<'a, Result<AppliedMigration, Error>> {
+        Box::pin(async move {
+            let mut tx = self.pool().begin().await.into_error()?;
+            let conn = tx.acquire().await.into_error()?;
+
+            let now = Instant::now();
+
+            log::debug!("applying migration {}...", migration.version);
+            (migration.f)(conn).await?;
+            let duration_ms = now.elapsed().as_millis() as i64;
+
+            let applied = migration.new_applied(duration_ms);
+            let history = self.history_table();
+            let insert_sql = history.insert_into_query(&applied).clone();
+
+            log::debug!("migration {} applied", migration.version);
+            sqlx::query(&insert_sql)
+                .bind(applied.version)
+                .bind(applied.description.clone())
+                .bind(applied.content.clone())
+                .bind(applied.checksum.clone())
+                .bind(applied.duration_ms)
+                .execute(&mut *conn)
+                .await
+                .into_error_void()?;
+
+            tx.commit().await.into_error()?;
+
+            Ok(applied)
+        })
+    }
+
+    fn revert_no_tx<'a, 'c: 'a>(
+        &'c mut self,
+        migration: &'a Migration,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let Some(statements) = migration.down_statements.clone() else {
+                return Err(Error::Validation(format!(
+                    "migration {} has no down migration to revert",
+                    migration.version
+                )));
+            };
+
+            log::debug!("reverting migration {}...", migration.version);
+            for statement in statements.iter() {
+                self.pool()
+                    .execute(sqlx::raw_sql(statement.as_ref()))
+                    .await
+                    .into_error_with(migration)?;
+            }
+
+            log::debug!("migration {} reverted", migration.version);
+            let history = self.history_table();
+            let delete_sql = history.delete_from_query();
+            sqlx::query(&delete_sql)
+                .bind(migration.version)
+                .execute(self.pool())
+                .await
+                .into_error_void()?;
+
+            Ok(())
+        })
+    }
+
+    fn revert_tx<'a, 'c: 'a>(
+        &'c mut self,
+        migration: &'a Migration,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let Some(sql) = migration.down_sql.clone() else {
+                return Err(Error::Validation(format!(
+                    "migration {} has no down migration to revert",
+                    migration.version
+                )));
+            };
+            let mut tx = self.pool().begin().await.into_error()?;
+            let conn = tx.acquire().await.into_error()?;
+
+            log::debug!("reverting migration {}...", migration.version);
+            conn.execute(sqlx::raw_sql(&sql))
+                .await
+                .into_error_with(migration)?;
+
+            log::debug!("migration {} reverted", migration.version);
+            let history = self.history_table();
+            let delete_sql = history.delete_from_query();
+            sqlx::query(&delete_sql)
+                .bind(migration.version)
+                .execute(&mut *conn)
+                .await
+                .into_error_void()?;
+
+            tx.commit().await.into_error()?;
+
+            Ok(())
+        })
+    }
+
+    fn validate_source(
+        source: Vec<MigrationSource>,
+        history: Vec<ExistingMigration>,
+    ) -> Result<(), Error> {
+        Validate::run_validation(source, history)
+    }
+}
+
+impl SqlxSqliteMigrate {
+    pub fn new(pool: SqlitePool, history_table: SqlxSqliteHistoryTable) -> Self {
+        Self {
+            pool,
+            history_table,
+        }
+    }
+
+    pub fn pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+
+    pub fn history_table(&self) -> &SqlxSqliteHistoryTable {
+        &self.history_table
+    }
+}
+
+impl SqlxSqliteHistoryTable {
+    pub fn new(name: String) -> Self {
+        Self { name }
+    }
+
+    pub fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn delete_from_query(&self) -> String {
+        format!("DELETE FROM {} WHERE version = ?;", self.name())
+    }
+}
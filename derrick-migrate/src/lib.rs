@@ -0,0 +1,5 @@
+pub mod backends;
+pub mod migrate;
+pub mod runner;
+
+pub use runner::{AnyRunner, Runner};
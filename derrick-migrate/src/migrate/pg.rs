@@ -0,0 +1,42 @@
+/// Postgres-specific DDL and queries for the schema history table.
+pub struct PgHistoryTableOptions {
+    name: String,
+}
+
+impl PgHistoryTableOptions {
+    pub fn new(name: String) -> Self {
+        Self { name }
+    }
+
+    pub fn create_if_not_exists_query(&self) -> String {
+        format!(
+            "
+CREATE TABLE IF NOT EXISTS {}(
+  version BIGINT PRIMARY KEY,
+  description TEXT NOT NULL,
+  content TEXT NOT NULL,
+  checksum TEXT NOT NULL DEFAULT '',
+  duration_ms BIGINT NOT NULL
+);",
+            self.name,
+        )
+    }
+
+    /// Add the `checksum` column to a history table that was created
+    /// before this column existed, so upgrading to a checksum-aware
+    /// derrick does not require recreating the table by hand.
+    ///
+    /// `create_if_not_exists_query` already declares the column for a
+    /// brand new table; this covers the table that already exists
+    /// from before this change.
+    pub fn add_checksum_column_query(&self) -> String {
+        format!(
+            "ALTER TABLE {} ADD COLUMN IF NOT EXISTS checksum TEXT NOT NULL DEFAULT '';",
+            self.name,
+        )
+    }
+
+    pub fn select_star_from_query(&self) -> String {
+        format!("SELECT * FROM {};", self.name)
+    }
+}
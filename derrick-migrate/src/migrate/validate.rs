@@ -0,0 +1,61 @@
+use derrick_core::error::Error;
+use derrick_core::types::{checksum, ExistingMigration, MigrationSource};
+
+/// The default `validate_source` behavior shared by the `sqlx`
+/// backends.
+pub struct Validate;
+
+impl Validate {
+    pub fn run_validation(
+        source: Vec<MigrationSource>,
+        history: Vec<ExistingMigration>,
+    ) -> Result<(), Error> {
+        Self::validate_order(&source)?;
+        Self::validate_checksums(&source, &history)?;
+
+        Ok(())
+    }
+
+    /// Source migrations must not declare the same version twice.
+    fn validate_order(source: &[MigrationSource]) -> Result<(), Error> {
+        let mut versions: Vec<i64> = source.iter().map(|m| m.version).collect();
+        versions.sort_unstable();
+
+        for pair in versions.windows(2) {
+            let (prev, next) = (pair[0], pair[1]);
+            if next == prev {
+                return Err(Error::Validation(format!(
+                    "duplicate migration version {next}"
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// A migration that has already been applied must still match the
+    /// source it was applied from, or the source file was edited
+    /// after the fact and the applied schema may no longer reflect
+    /// it.
+    fn validate_checksums(
+        source: &[MigrationSource],
+        history: &[ExistingMigration],
+    ) -> Result<(), Error> {
+        for applied in history {
+            let Some(current) = source.iter().find(|m| m.version == applied.version) else {
+                continue;
+            };
+
+            let recomputed = checksum(&current.content);
+            if recomputed != applied.checksum {
+                return Err(Error::Validation(format!(
+                    "migration {} has been edited since it was applied \
+                     (checksum {recomputed} does not match the recorded {})",
+                    applied.version, applied.checksum
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,153 @@
+use derrick_core::error::Error;
+use derrick_core::prelude::{HistoryTable, Migrate};
+use derrick_core::types::{FutureMigration, HistoryTableOptions, Migration, MigrationSource};
+
+use crate::backends::sqlx::mysql::{SqlxMysqlHistoryTable, SqlxMysqlMigrate};
+use crate::backends::sqlx::postgres::{SqlxPgHistoryTable, SqlxPgMigrate};
+use crate::backends::sqlx::sqlite::{SqlxSqliteHistoryTable, SqlxSqliteMigrate};
+
+/// Drives a `Migrate` backend through a batch of migrations.
+pub struct Runner<M: Migrate> {
+    migrate: M,
+}
+
+impl<M: Migrate> Runner<M> {
+    pub fn new(migrate: M) -> Self {
+        Self { migrate }
+    }
+
+    /// Apply every migration in `migrations` that is newer than the
+    /// current history, in order.
+    ///
+    /// A session lock is held for the whole batch so concurrent runs
+    /// against the same database do not race on the history table;
+    /// it is released even if a migration fails partway through,
+    /// rather than only on the success path.
+    pub async fn run(&mut self, migrations: &[Migration]) -> Result<(), Error> {
+        self.migrate.check_history_table().await?;
+        self.migrate.lock().await?;
+
+        let result = self.validate_and_apply(migrations).await;
+
+        // Always release the lock, even on the error path, so a
+        // crashed run does not leave it held for the session's
+        // lifetime.
+        let unlock_result = self.migrate.unlock().await;
+
+        result.and(unlock_result)
+    }
+
+    /// Check the source migrations against the history table - most
+    /// importantly, that a migration already applied has not since
+    /// been edited - before applying anything.
+    async fn validate_and_apply(&mut self, migrations: &[Migration]) -> Result<(), Error> {
+        let history = self.migrate.get_history_table().await?;
+        let source = migrations.iter().map(MigrationSource::from).collect();
+        M::validate_source(source, history)?;
+
+        self.apply_all(migrations).await
+    }
+
+    async fn apply_all(&mut self, migrations: &[Migration]) -> Result<(), Error> {
+        let current = self.migrate.current_version().await?;
+
+        for migration in migrations {
+            if current.map_or(true, |v| migration.version > v) {
+                self.migrate.apply(migration).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Apply every Rust-defined (function) migration in `migrations`
+    /// that is newer than the current history, in order, dispatching
+    /// to `apply_fn` the same way `run` dispatches SQL migrations to
+    /// `apply`.
+    pub async fn run_fn(&mut self, migrations: Vec<FutureMigration<M::Conn>>) -> Result<(), Error> {
+        self.migrate.check_history_table().await?;
+        self.migrate.lock().await?;
+
+        let result = self.validate_and_apply_fn(migrations).await;
+
+        let unlock_result = self.migrate.unlock().await;
+
+        result.and(unlock_result)
+    }
+
+    async fn validate_and_apply_fn(
+        &mut self,
+        migrations: Vec<FutureMigration<M::Conn>>,
+    ) -> Result<(), Error> {
+        let history = self.migrate.get_history_table().await?;
+        let source = migrations.iter().map(MigrationSource::from).collect();
+        M::validate_source(source, history)?;
+
+        self.apply_all_fn(migrations).await
+    }
+
+    async fn apply_all_fn(&mut self, migrations: Vec<FutureMigration<M::Conn>>) -> Result<(), Error> {
+        let current = self.migrate.current_version().await?;
+
+        for migration in migrations {
+            if current.map_or(true, |v| migration.version > v) {
+                self.migrate.apply_fn(migration).await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A `Runner` over one of the built-in `sqlx` backends, selected at
+/// runtime by the scheme of the database URL rather than chosen at
+/// compile time with a generic parameter.
+pub enum AnyRunner {
+    Postgres(Runner<SqlxPgMigrate>),
+    Sqlite(Runner<SqlxSqliteMigrate>),
+    Mysql(Runner<SqlxMysqlMigrate>),
+}
+
+impl AnyRunner {
+    /// Connect and build the `Runner` whose backend matches the URL's
+    /// scheme (`postgres://`, `sqlite://`, `mysql://`).  For Postgres
+    /// the target database is first created if missing; the SQLite
+    /// and MySQL backends do not create it.
+    pub async fn connect(db_url: String, history_table: String) -> Result<Self, Error> {
+        let options = HistoryTableOptions::new(history_table);
+        let scheme = db_url.split_once("://").map(|(scheme, _)| scheme);
+
+        match scheme {
+            Some("postgres") | Some("postgresql") => {
+                // Create the target database if it doesn't exist yet
+                // before `initialize` opens the real pool, so a fresh
+                // environment doesn't need a manual `createdb` first.
+                SqlxPgMigrate::create_database_if_not_exists(db_url.clone()).await?;
+                let history = <SqlxPgHistoryTable as HistoryTable>::new(&options);
+                let migrate = SqlxPgMigrate::initialize(db_url, history, ()).await?;
+                Ok(Self::Postgres(Runner::new(migrate)))
+            }
+            Some("sqlite") => {
+                let history = <SqlxSqliteHistoryTable as HistoryTable>::new(&options);
+                let migrate = SqlxSqliteMigrate::initialize(db_url, history, ()).await?;
+                Ok(Self::Sqlite(Runner::new(migrate)))
+            }
+            Some("mysql") => {
+                let history = <SqlxMysqlHistoryTable as HistoryTable>::new(&options);
+                let migrate = SqlxMysqlMigrate::initialize(db_url, history, ()).await?;
+                Ok(Self::Mysql(Runner::new(migrate)))
+            }
+            _ => Err(Error::Validation(format!(
+                "unsupported database url scheme in {db_url}"
+            ))),
+        }
+    }
+
+    pub async fn run(&mut self, migrations: &[Migration]) -> Result<(), Error> {
+        match self {
+            Self::Postgres(runner) => runner.run(migrations).await,
+            Self::Sqlite(runner) => runner.run(migrations).await,
+            Self::Mysql(runner) => runner.run(migrations).await,
+        }
+    }
+}